@@ -0,0 +1,181 @@
+//! `easytiming::decorator` lets callers plug in how a finished `Timing` is
+//! rendered — colors, alignment, units — instead of the fixed free-text
+//! line. Modeled on slog-term's open `Decorator` trait. Enabled by feature
+//! 'decorator'.
+//!
+//! Quick start
+//!
+//! ```rust,ignore
+//! use easytiming::Timing;
+//! use easytiming::decorator::TermDecorator;
+//!
+//! let _t: Timing = Timing::new("slow_query").decorator(TermDecorator::new());
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The warn/error elapsed-time thresholds a [`TermDecorator`] colors against.
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    pub warn: Duration,
+    pub error: Duration,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            warn: Duration::from_millis(100),
+            error: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Controls how a finished `Timing` is rendered to an output stream: colors,
+/// alignment, units. An open trait, like slog-term's `Decorator`, so callers
+/// can supply their own rendering without forking `easytiming`.
+pub trait Decorator: Send + Sync + fmt::Debug {
+    /// Writes a single display line for `name`'s `lapse` to `out`.
+    fn decorate(&self, name: &str, lapse: Duration, out: &mut dyn Write) -> io::Result<()>;
+}
+
+fn ansi_code_for(lapse: Duration, thresholds: Thresholds) -> &'static str {
+    if lapse >= thresholds.error {
+        "31" // red
+    } else if lapse >= thresholds.warn {
+        "33" // yellow
+    } else {
+        "32" // green
+    }
+}
+
+/// The default `Decorator`: colors the line green/yellow/red depending on
+/// how `lapse` compares to its `Thresholds`, but only when stdout is a TTY,
+/// so piped/redirected output stays plain.
+#[derive(Debug, Clone)]
+pub struct TermDecorator {
+    thresholds: Thresholds,
+    color: bool,
+}
+
+impl Default for TermDecorator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TermDecorator {
+    /// Detects whether stdout is a TTY and colors accordingly, using the
+    /// default warn/error thresholds.
+    pub fn new() -> Self {
+        Self::with_thresholds(Thresholds::default())
+    }
+
+    /// Like `new`, but with explicit warn/error thresholds.
+    pub fn with_thresholds(thresholds: Thresholds) -> Self {
+        Self {
+            thresholds,
+            color: atty::is(atty::Stream::Stdout),
+        }
+    }
+}
+
+impl Decorator for TermDecorator {
+    fn decorate(&self, name: &str, lapse: Duration, out: &mut dyn Write) -> io::Result<()> {
+        let line = format!("\"{}\" was running for {:?}", name, lapse);
+        if self.color {
+            writeln!(out, "\x1b[{}m{}\x1b[0m", ansi_code_for(lapse, self.thresholds), line)
+        } else {
+            writeln!(out, "{}", line)
+        }
+    }
+}
+
+/// Wraps another `Decorator` and serializes writes behind a `Mutex`, so that
+/// several threads sharing one output handle don't interleave lines. Mirrors
+/// slog-term's `PlainSyncDecorator`.
+pub struct PlainSyncDecorator<D> {
+    inner: D,
+    lock: Mutex<()>,
+}
+
+impl<D> fmt::Debug for PlainSyncDecorator<D>
+where
+    D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PlainSyncDecorator")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<D> PlainSyncDecorator<D>
+where
+    D: Decorator,
+{
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner,
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl<D> Decorator for PlainSyncDecorator<D>
+where
+    D: Decorator,
+{
+    fn decorate(&self, name: &str, lapse: Duration, out: &mut dyn Write) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        self.inner.decorate(name, lapse, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_line_has_no_escapes() {
+        let decorator = TermDecorator {
+            thresholds: Thresholds::default(),
+            color: false,
+        };
+        let mut out = Vec::new();
+        decorator.decorate("slow_query", Duration::from_millis(5), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\"slow_query\" was running for 5ms\n"
+        );
+    }
+
+    #[test]
+    fn colored_line_picks_threshold() {
+        let decorator = TermDecorator {
+            thresholds: Thresholds::default(),
+            color: true,
+        };
+        let mut out = Vec::new();
+        decorator
+            .decorate("slow_query", Duration::from_secs(2), &mut out)
+            .unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("\x1b[31m"));
+    }
+
+    #[test]
+    fn plain_sync_decorator_delegates() {
+        let decorator = PlainSyncDecorator::new(TermDecorator {
+            thresholds: Thresholds::default(),
+            color: false,
+        });
+        let mut out = Vec::new();
+        decorator.decorate("fast_query", Duration::from_millis(1), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\"fast_query\" was running for 1ms\n"
+        );
+    }
+}
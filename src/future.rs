@@ -1,19 +1,14 @@
-//! 'easytiming::future::Timing' provides mean to measure time taken by future execution
+//! 'easytiming::future::Timing' provides a means to measure the time taken by
+//! a `std::future::Future`'s execution.
 //! It is enabled by feature 'futures'
 //!
 //! Quick start
 //!
-//! ```rust
-//! extern crate futures;
-//! extern crate easytiming;
-//!
+//! ```rust,ignore
 //! use easytiming::future::FutureExt;
-//! use easytiming::future::Timing;
-//! use futures::future::ok;
 //!
-//! fn main() {
-//!     let ok = ok::<u8, u8>(1);
-//!     let future = ok.timing("ok future");
+//! async fn run() {
+//!     let value = async { 1u8 }.timing("ok future").await;
 //!
 //!     // Do some important stuff here
 //!     // ...
@@ -21,20 +16,24 @@
 //! ```
 
 use std::fmt;
+use std::future::Future;
 use std::io::{Stdout, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 use std::borrow::Cow;
 
-use futures::{Future, Async, Poll};
+use pin_project::pin_project;
 
-use super::Sink;
+use super::{format_exec_time, Sink};
 
+#[pin_project(PinnedDrop)]
 #[derive(Debug)]
 pub struct Timing<'a, A, W = Stdout>
 where
-    A: Future,
     W: Write,
 {
+    #[pin]
     inner: A,
     start: Instant,
     completed: Option<Instant>,
@@ -46,7 +45,6 @@ where
 
 impl<'a, A, W> fmt::Display for Timing<'a, A, W>
 where
-    A: Future,
     W: Write,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -61,7 +59,6 @@ where
 
 impl<'a, A, W> Timing<'a, A, W>
 where
-    A: Future,
     W: Write,
 {
     pub fn new<N>(inner: A, name: N) -> Self
@@ -105,6 +102,23 @@ where
         timing
     }
 
+    /// Creates a `Timing` that reports a single-line JSON record when dropped
+    /// instead of the default free-text line, matching `easytiming::Timing::json`.
+    pub fn json<N>(inner: A, name: N) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        Self {
+            inner,
+            start: Instant::now(),
+            completed: None,
+            lapse: Duration::default(),
+            name: name.into(),
+            quiet: false,
+            sink: Sink::Json,
+        }
+    }
+
     #[inline]
     fn elapsed(&self) -> Duration {
         Instant::now() - self.start
@@ -124,15 +138,32 @@ where
         self.name.as_ref()
     }
 
-    fn report(&mut self) {
-        let output = format!(
+    fn format_text(&self) -> String {
+        format!(
             "\"{}\" was running for {} ns",
             self.name,
             self.lapse.subsec_nanos()
-        );
+        )
+    }
+
+    fn format_json(&self) -> String {
+        format!(
+            "{{\"type\":\"timing\",\"name\":\"{}\",\"event\":\"ok\",\"exec_time\":\"{}\"}}",
+            self.name,
+            format_exec_time(self.lapse)
+        )
+    }
+
+    fn report(&mut self) {
+        let output = match self.sink {
+            Sink::Json => self.format_json(),
+            _ => self.format_text(),
+        };
         match self.sink {
-            Sink::Println => println!("{}", output),
+            Sink::Println | Sink::Json => println!("{}", output),
             Sink::Writer(ref mut out) => write!(out, "{}", output).unwrap(),
+            #[cfg(influx)]
+            Sink::Influx(_) => {}
             #[cfg(log)]
             Sink::Log => trace!(output),
             #[cfg(slog)]
@@ -141,30 +172,31 @@ where
     }
 }
 
-impl<'a, A, W> Drop for Timing<'a, A, W>
+#[pin_project::pinned_drop]
+impl<'a, A, W> PinnedDrop for Timing<'a, A, W>
 where
-    A: Future,
     W: Write,
 {
-    fn drop(&mut self) {
-        self.finish()
+    fn drop(self: Pin<&mut Self>) {
+        // `finish`/`report` only touch the non-structural fields (`lapse`,
+        // `name`, `sink`, ...) and never move or access the pinned `inner`
+        // future, so treating `self` as unpinned here is sound.
+        unsafe { self.get_unchecked_mut() }.finish()
     }
 }
 
-
 impl<'a, A, W> Future for Timing<'a, A, W>
 where
     A: Future,
-    W: Write
+    W: Write,
 {
-    type Item = A::Item;
-    type Error = A::Error;
-
-    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-        let poll = self.inner.poll();
-        match poll {
-            Ok(Async::Ready(_)) | Err(_) => self.completed = Some(Instant::now()),
-            _ => (),
+    type Output = A::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let poll = this.inner.poll(cx);
+        if poll.is_ready() {
+            *this.completed = Some(Instant::now());
         }
         poll
     }
@@ -179,7 +211,7 @@ pub trait FutureExt: Future {
 
 impl<F> FutureExt for F
 where
-    F: Future
+    F: Future,
 {
     fn timing<'a, N>(self, name: N) -> Timing<'a, Self>
     where
@@ -193,14 +225,32 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::future::ok;
+    use futures_executor::block_on;
 
     const NAME: &str = "timing";
 
     #[test]
     fn fromok() {
-        let ok = ok::<u64, u64>(1);
-        let t = ok.timing(NAME);
+        let ready = std::future::ready(1u64);
+        let t = ready.timing(NAME);
         assert_eq!(t.name(), NAME);
     }
+
+    #[test]
+    fn resolves() {
+        let ready = std::future::ready(1u64);
+        let value = block_on(ready.timing(NAME));
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn json() {
+        let ready = std::future::ready(1u64);
+        let mut t: Timing<'_, _, std::io::Stdout> = Timing::json(ready, NAME);
+        t.lapse = Duration::from_millis(100);
+        assert_eq!(
+            t.format_json(),
+            "{\"type\":\"timing\",\"name\":\"timing\",\"event\":\"ok\",\"exec_time\":\"0.100s\"}"
+        );
+    }
 }
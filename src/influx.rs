@@ -0,0 +1,205 @@
+//! `easytiming::influx` ships timing measurements as InfluxDB line-protocol
+//! points instead of printing a free-text line. Enabled by feature 'influx'.
+//!
+//! Quick start
+//!
+//! ```rust,ignore
+//! use easytiming::Timing;
+//! use easytiming::influx::InfluxSink;
+//!
+//! let sink = InfluxSink::new(std::io::stdout(), "timing");
+//! let _t = Timing::influx("do_something", sink).tag("service", "api");
+//! ```
+
+use std::borrow::Cow;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+
+/// A single InfluxDB line-protocol point queued for a flush.
+pub(crate) struct Point {
+    name: Cow<'static, str>,
+    tags: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    value_ns: u64,
+    timestamp_ns: u128,
+}
+
+impl Point {
+    pub(crate) fn new(
+        name: Cow<'static, str>,
+        tags: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+        value_ns: u64,
+        timestamp_ns: u128,
+    ) -> Self {
+        Self {
+            name,
+            tags,
+            value_ns,
+            timestamp_ns,
+        }
+    }
+
+    fn write_line_protocol(&self, measurement: &str, out: &mut dyn Write) -> std::io::Result<()> {
+        write!(
+            out,
+            "{},name={}",
+            escape_measurement(measurement),
+            escape_key_or_tag(&self.name)
+        )?;
+        for (key, value) in &self.tags {
+            write!(out, ",{}={}", escape_key_or_tag(key), escape_key_or_tag(value))?;
+        }
+        writeln!(out, " value={}i {}", self.value_ns, self.timestamp_ns)
+    }
+}
+
+/// Escapes a measurement name per the InfluxDB line-protocol spec: commas
+/// and spaces separate the measurement from tags/fields, so both must be
+/// backslash-escaped (backslash itself escaped first, to avoid double
+/// escaping the backslashes this introduces).
+fn escape_measurement(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace(' ', "\\ ")
+}
+
+/// Escapes a tag key, tag value, or the synthetic `name` tag per the
+/// InfluxDB line-protocol spec: commas, equals signs, and spaces all have
+/// syntactic meaning in the tag set and must be backslash-escaped.
+fn escape_key_or_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// Controls how eagerly the background flusher drains batched points: either
+/// once `max_batch` points have queued up, or every `interval`, whichever
+/// comes first.
+#[derive(Debug, Clone, Copy)]
+pub struct FlushPolicy {
+    pub max_batch: usize,
+    pub interval: Duration,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        Self {
+            max_batch: 100,
+            interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// A cheap, cloneable handle to a background thread that batches points and
+/// writes them to an injected `Write` sink, so a `Timing`'s `Drop` never
+/// blocks on I/O.
+#[derive(Debug, Clone)]
+pub struct InfluxSink {
+    sender: Sender<Point>,
+}
+
+impl InfluxSink {
+    /// Spawns the flushing background thread writing to `writer`, using the
+    /// default `FlushPolicy`.
+    pub fn new<N, W>(writer: W, measurement: N) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        W: Write + Send + 'static,
+    {
+        Self::with_policy(writer, measurement, FlushPolicy::default())
+    }
+
+    /// Like `new`, but with an explicit flush interval/batch size.
+    pub fn with_policy<N, W>(writer: W, measurement: N, policy: FlushPolicy) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        W: Write + Send + 'static,
+    {
+        let measurement = measurement.into();
+        let (sender, receiver) = bounded(policy.max_batch * 4);
+        thread::spawn(move || Self::flush_loop(writer, measurement, receiver, policy));
+        Self { sender }
+    }
+
+    /// Queues `point` for the background flusher. Cheap: a non-blocking
+    /// channel send off the hot path; a full queue simply drops the point
+    /// rather than blocking the caller's `Drop`.
+    pub(crate) fn send(&self, point: Point) {
+        let _ = self.sender.try_send(point);
+    }
+
+    fn flush_loop<W: Write>(
+        mut writer: W,
+        measurement: Cow<'static, str>,
+        receiver: Receiver<Point>,
+        policy: FlushPolicy,
+    ) {
+        let mut batch = Vec::with_capacity(policy.max_batch);
+        let mut deadline = Instant::now() + policy.interval;
+        loop {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(point) => {
+                    batch.push(point);
+                    if batch.len() >= policy.max_batch {
+                        Self::flush(&mut writer, &measurement, &mut batch);
+                        deadline = Instant::now() + policy.interval;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    Self::flush(&mut writer, &measurement, &mut batch);
+                    deadline = Instant::now() + policy.interval;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    Self::flush(&mut writer, &measurement, &mut batch);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn flush<W: Write>(writer: &mut W, measurement: &str, batch: &mut Vec<Point>) {
+        for point in batch.drain(..) {
+            let _ = point.write_line_protocol(measurement, writer);
+        }
+        let _ = writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_protocol_format() {
+        let point = Point::new(
+            Cow::Borrowed("do_something"),
+            vec![(Cow::Borrowed("service"), Cow::Borrowed("api"))],
+            100,
+            1_600_000_000_000_000_000,
+        );
+        let mut out = Vec::new();
+        point.write_line_protocol("timing", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timing,name=do_something,service=api value=100i 1600000000000000000\n"
+        );
+    }
+
+    #[test]
+    fn line_protocol_escapes_special_characters() {
+        let point = Point::new(
+            Cow::Borrowed("do_something() function"),
+            vec![(Cow::Borrowed("a,b"), Cow::Borrowed("c=d"))],
+            100,
+            1,
+        );
+        let mut out = Vec::new();
+        point.write_line_protocol("timing", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timing,name=do_something()\\ function,a\\,b=c\\=d value=100i 1\n"
+        );
+    }
+}
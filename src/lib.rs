@@ -22,23 +22,52 @@ extern crate log;
 #[cfg(slog)]
 #[macro_use]
 extern crate slog;
+#[cfg(any(registry, influx))]
+extern crate crossbeam_channel;
+#[cfg(registry)]
+extern crate hdrhistogram;
+#[cfg(decorator)]
+extern crate atty;
 
+#[cfg(futures)]
+pub mod future;
+#[cfg(registry)]
+pub mod registry;
+#[cfg(influx)]
+pub mod influx;
+#[cfg(futures03)]
+pub mod stream;
+#[cfg(decorator)]
+pub mod decorator;
 
 use std::fmt;
 use std::io::{Stdout, Write};
 use std::time;
 use std::borrow::Cow;
+#[cfg(decorator)]
+use std::io;
+#[cfg(decorator)]
+use std::sync::Arc;
 
 #[derive(Debug)]
 enum Sink<W> where W: Write {
     Println,
     Writer(W),
+    Json,
+    #[cfg(influx)]
+    Influx(influx::InfluxSink),
     #[cfg(log)]
     Log,
     #[cfg(slog)]
     Slog,
 }
 
+/// Renders a `Duration` as a libtest-style `"<seconds>.<fraction>s"` string,
+/// e.g. `0.100s`, using the full duration instead of just the sub-second part.
+fn format_exec_time(d: time::Duration) -> String {
+    format!("{:.3}s", d.as_secs_f64())
+}
+
 #[derive(Debug)]
 pub struct Timing<'a, W = Stdout>
 where
@@ -49,6 +78,12 @@ where
     name: Cow<'a, str>,
     quiet: bool,
     sink: Sink<W>,
+    #[cfg(registry)]
+    registry: Option<registry::TimingRegistry>,
+    #[cfg(influx)]
+    tags: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    #[cfg(decorator)]
+    decorator: Arc<dyn decorator::Decorator>,
 }
 
 impl<'a, W> Default for Timing<'a, W>
@@ -62,6 +97,12 @@ where
             name: Default::default(),
             quiet: false,
             sink: Sink::Println,
+            #[cfg(registry)]
+            registry: None,
+            #[cfg(influx)]
+            tags: Vec::new(),
+            #[cfg(decorator)]
+            decorator: Arc::new(decorator::TermDecorator::new()),
         }
     }
 }
@@ -99,6 +140,33 @@ where
         timing
     }
 
+    /// Creates a `Timing` that reports a single-line JSON record on `Drop`
+    /// instead of the default free-text line, e.g.
+    /// `{"type":"timing","name":"do_something","event":"ok","exec_time":"0.100s"}`.
+    pub fn json<N>(name: N) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        let mut timing = Timing::default();
+        timing.name = name.into();
+        timing.sink = Sink::Json;
+        timing
+    }
+
+    /// Creates a `Timing` that, on `Drop`, records its elapsed time into
+    /// `registry` instead of printing a line, so that many repeated
+    /// measurements of the same name aggregate into a single histogram.
+    #[cfg(registry)]
+    pub fn with_registry<N>(name: N, registry: registry::TimingRegistry) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        let mut timing = Timing::default();
+        timing.name = name.into();
+        timing.registry = Some(registry);
+        timing
+    }
+
     pub fn with_writer<N>(name: N, writer: W) -> Self
     where
         N: Into<Cow<'a, str>>,
@@ -109,6 +177,42 @@ where
         timing
     }
 
+    /// Creates a `Timing` that, on `Drop`, ships its elapsed time to `sink`
+    /// as an InfluxDB line-protocol point instead of printing a line.
+    #[cfg(influx)]
+    pub fn influx<N>(name: N, sink: influx::InfluxSink) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        let mut timing = Self::default();
+        timing.name = name.into();
+        timing.sink = Sink::Influx(sink);
+        timing
+    }
+
+    /// Attaches an InfluxDB tag to the point reported for this measurement.
+    /// Only meaningful when this `Timing` was built with [`Timing::influx`].
+    #[cfg(influx)]
+    pub fn tag<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Overrides the [`decorator::Decorator`] used to render this `Timing`
+    /// when its sink is [`Sink::Println`] (the default sink).
+    #[cfg(decorator)]
+    pub fn decorator<D>(mut self, decorator: D) -> Self
+    where
+        D: decorator::Decorator + 'static,
+    {
+        self.decorator = Arc::new(decorator);
+        self
+    }
+
     #[cfg(log)]
     pub fn with_writer<N>(name: N, writer: W) -> Self
     where
@@ -128,6 +232,29 @@ where
     #[inline]
     fn finish(&mut self) {
         self.lapse = self.elapsed();
+        #[cfg(registry)]
+        {
+            if let Some(ref registry) = self.registry {
+                registry.record(self.name.clone().into_owned(), self.lapse.as_nanos() as u64);
+                return;
+            }
+        }
+        #[cfg(influx)]
+        {
+            if let Sink::Influx(ref sink) = self.sink {
+                let timestamp_ns = time::SystemTime::now()
+                    .duration_since(time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos();
+                sink.send(influx::Point::new(
+                    self.name.clone().into_owned().into(),
+                    std::mem::take(&mut self.tags),
+                    self.lapse.as_nanos() as u64,
+                    timestamp_ns,
+                ));
+                return;
+            }
+        }
         if self.quiet {
             return;
         }
@@ -139,15 +266,42 @@ where
         self.name.as_ref()
     }
 
-    fn report(&mut self) {
-        let output = format!(
+    fn format_text(&self) -> String {
+        format!(
             "\"{}\" was running for {} ns",
             self.name,
             self.lapse.subsec_nanos()
-        );
+        )
+    }
+
+    fn format_json(&self) -> String {
+        format!(
+            "{{\"type\":\"timing\",\"name\":\"{}\",\"event\":\"ok\",\"exec_time\":\"{}\"}}",
+            self.name,
+            format_exec_time(self.lapse)
+        )
+    }
+
+    fn report(&mut self) {
+        #[cfg(decorator)]
+        {
+            if let Sink::Println = self.sink {
+                let mut stdout = io::stdout();
+                let _ = self.decorator.decorate(&self.name, self.lapse, &mut stdout);
+                return;
+            }
+        }
+        let output = match self.sink {
+            Sink::Json => self.format_json(),
+            _ => self.format_text(),
+        };
         match self.sink {
-            Sink::Println => println!("{}", output),
+            Sink::Println | Sink::Json => println!("{}", output),
             Sink::Writer(ref mut out) => write!(out, "{}", output).unwrap(),
+            // Points are sent directly to the `InfluxSink` from `finish()`,
+            // which returns before `report()` is ever reached.
+            #[cfg(influx)]
+            Sink::Influx(_) => {}
             #[cfg(log)]
             Sink::Log => trace!(output),
             #[cfg(slog)]
@@ -200,4 +354,38 @@ mod tests {
         let t: Timing = Timing::quiet();
         assert_eq!(t.name, "");
     }
+
+    #[test]
+    #[cfg(registry)]
+    fn with_registry() {
+        let registry = registry::TimingRegistry::new();
+        let t: Timing = Timing::with_registry(NAME, registry.clone());
+        assert_eq!(t.name(), NAME);
+    }
+
+    #[test]
+    #[cfg(influx)]
+    fn influx_tags() {
+        let sink = influx::InfluxSink::new(std::io::sink(), "timing");
+        let t: Timing = Timing::influx(NAME, sink).tag("service", "api");
+        assert_eq!(t.name(), NAME);
+        assert_eq!(t.tags, vec![(Cow::Borrowed("service"), Cow::Borrowed("api"))]);
+    }
+
+    #[test]
+    #[cfg(decorator)]
+    fn custom_decorator() {
+        let t: Timing = Timing::new(NAME).decorator(decorator::TermDecorator::new());
+        assert_eq!(t.name(), NAME);
+    }
+
+    #[test]
+    fn json() {
+        let mut t: Timing = Timing::json(NAME);
+        t.lapse = time::Duration::from_millis(100);
+        assert_eq!(
+            t.format_json(),
+            "{\"type\":\"timing\",\"name\":\"timing\",\"event\":\"ok\",\"exec_time\":\"0.100s\"}"
+        );
+    }
 }
@@ -0,0 +1,145 @@
+//! `easytiming::registry` aggregates timing samples from many `Timing` instances
+//! into per-name latency histograms, instead of having each `Timing` print a line
+//! on every `Drop`. Enabled by feature 'registry'.
+//!
+//! Quick start
+//!
+//! ```rust,ignore
+//! use easytiming::Timing;
+//! use easytiming::registry::TimingRegistry;
+//!
+//! let registry = TimingRegistry::new();
+//! for _ in 0..1_000_000 {
+//!     let _t = Timing::with_registry("hot_path", registry.clone());
+//!     // ... do work ...
+//! }
+//! registry.report();
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::thread;
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use hdrhistogram::Histogram;
+
+/// Highest latency, in nanoseconds, the registry's histograms can represent.
+/// Samples above this are clamped rather than causing the histogram to panic.
+const MAX_NANOS: u64 = 60 * 1_000_000_000;
+const SIGFIG: u8 = 3;
+
+enum Message {
+    Sample(Cow<'static, str>, u64),
+    Report(Sender<String>),
+}
+
+/// A cheap, cloneable handle to a background aggregator thread that merges
+/// samples from many `Timing` instances into per-name HDR histograms.
+///
+/// Recording a sample is just a channel send, so it stays cheap even when a
+/// `Timing` is created millions of times; the actual histogram bucketing
+/// happens off the hot path, on the aggregator thread.
+#[derive(Debug, Clone)]
+pub struct TimingRegistry {
+    sender: Sender<Message>,
+}
+
+impl Default for TimingRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimingRegistry {
+    /// Spawns the aggregator thread and returns a handle to it.
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded();
+        thread::spawn(move || Self::aggregate(receiver));
+        Self { sender }
+    }
+
+    /// Records `nanos` for `name`, clamping to the registry's representable
+    /// range instead of letting the histogram panic on out-of-range values.
+    pub(crate) fn record<N>(&self, name: N, nanos: u64)
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        let nanos = nanos.min(MAX_NANOS);
+        let _ = self.sender.send(Message::Sample(name.into(), nanos));
+    }
+
+    /// Prints, per name, the sample count plus the p50/p90/p99/max latency
+    /// percentiles recorded so far.
+    ///
+    /// Blocks until the aggregator thread has drained every sample queued
+    /// before this call and rendered the report, so the print is guaranteed
+    /// to have happened by the time this returns.
+    pub fn report(&self) {
+        print!("{}", self.report_string());
+    }
+
+    /// Like `report`, but returns the rendered text instead of printing it.
+    fn report_string(&self) -> String {
+        let (ack_sender, ack_receiver) = bounded(1);
+        if self.sender.send(Message::Report(ack_sender)).is_err() {
+            return String::new();
+        }
+        ack_receiver.recv().unwrap_or_default()
+    }
+
+    fn aggregate(receiver: Receiver<Message>) {
+        let mut histograms: HashMap<Cow<'static, str>, Histogram<u64>> = HashMap::new();
+        for message in receiver {
+            match message {
+                Message::Sample(name, nanos) => {
+                    let histogram = histograms.entry(name).or_insert_with(|| {
+                        Histogram::new_with_bounds(1, MAX_NANOS, SIGFIG)
+                            .expect("registry histogram bounds are always valid")
+                    });
+                    let _ = histogram.record(nanos);
+                }
+                Message::Report(ack) => {
+                    let mut output = String::new();
+                    for (name, histogram) in &histograms {
+                        output.push_str(&format!(
+                            "\"{}\": count={} p50={}ns p90={}ns p99={}ns max={}ns\n",
+                            name,
+                            histogram.len(),
+                            histogram.value_at_quantile(0.50),
+                            histogram.value_at_quantile(0.90),
+                            histogram.value_at_quantile(0.99),
+                            histogram.max(),
+                        ));
+                    }
+                    let _ = ack.send(output);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_report_does_not_panic() {
+        let registry = TimingRegistry::new();
+        registry.record("hot_path", 100);
+        registry.record("hot_path", MAX_NANOS + 1);
+        registry.report();
+    }
+
+    #[test]
+    fn report_reflects_recorded_samples() {
+        let registry = TimingRegistry::new();
+        registry.record("hot_path", 100);
+        registry.record("hot_path", 200);
+        registry.record("cold_path", 50);
+
+        let output = registry.report_string();
+
+        assert!(output.contains("\"hot_path\": count=2"));
+        assert!(output.contains("\"cold_path\": count=1"));
+    }
+}
@@ -0,0 +1,130 @@
+//! `easytiming::stream` measures the lifetime and per-item cadence of a
+//! `futures` 0.3 `Stream`. Enabled by feature 'futures03'.
+//!
+//! Quick start
+//!
+//! ```rust,ignore
+//! use easytiming::stream::StreamExt;
+//! use futures::stream::{self, StreamExt as _};
+//!
+//! async fn run() {
+//!     let mut s = stream::iter(0..3).timing("ticks");
+//!     while s.next().await.is_some() {}
+//! }
+//! ```
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use pin_project::pin_project;
+
+/// Wraps a `Stream`, measuring total stream lifetime plus the gap between
+/// consecutive items, and prints a summary when dropped.
+///
+/// Reporting happens on `Drop` rather than the instant `poll_next` returns
+/// `Poll::Ready(None)`, so that a stream dropped mid-flight still reports
+/// what it saw.
+#[pin_project(PinnedDrop)]
+pub struct Timing<S> {
+    #[pin]
+    inner: S,
+    name: String,
+    start: Instant,
+    last_item: Option<Instant>,
+    count: u64,
+    total_gap: Duration,
+    min_gap: Option<Duration>,
+    max_gap: Option<Duration>,
+}
+
+impl<S> Timing<S> {
+    pub(crate) fn new(inner: S, name: String) -> Self {
+        Self {
+            inner,
+            name,
+            start: Instant::now(),
+            last_item: None,
+            count: 0,
+            total_gap: Duration::default(),
+            min_gap: None,
+            max_gap: None,
+        }
+    }
+}
+
+#[pin_project::pinned_drop]
+impl<S> PinnedDrop for Timing<S> {
+    fn drop(self: Pin<&mut Self>) {
+        // `count` items are separated by `count - 1` gaps.
+        let mean_gap = if self.count > 1 {
+            self.total_gap / (self.count - 1) as u32
+        } else {
+            Duration::default()
+        };
+        println!(
+            "\"{}\" stream ran for {:?}, {} item(s), gap mean={:?} min={:?} max={:?}",
+            self.name,
+            self.start.elapsed(),
+            self.count,
+            mean_gap,
+            self.min_gap.unwrap_or_default(),
+            self.max_gap.unwrap_or_default(),
+        );
+    }
+}
+
+impl<S> Stream for Timing<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match this.inner.poll_next(cx) {
+            Poll::Ready(Some(item)) => {
+                let now = Instant::now();
+                if let Some(last) = *this.last_item {
+                    let gap = now - last;
+                    *this.total_gap += gap;
+                    *this.min_gap = Some(this.min_gap.map_or(gap, |min| min.min(gap)));
+                    *this.max_gap = Some(this.max_gap.map_or(gap, |max| max.max(gap)));
+                }
+                *this.last_item = Some(now);
+                *this.count += 1;
+                Poll::Ready(Some(item))
+            }
+            // Spurious wakeups must not be counted as items, and the `None`
+            // terminal case is left to `Drop` to report.
+            other => other,
+        }
+    }
+}
+
+/// Extension trait adding the `.timing(name)` combinator to any `Stream`.
+pub trait StreamExt: Stream {
+    fn timing(self, name: impl Into<String>) -> Timing<Self>
+    where
+        Self: Sized,
+    {
+        Timing::new(self, name.into())
+    }
+}
+
+impl<S> StreamExt for S where S: Stream {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+    use futures_util::stream::{self, StreamExt as _};
+
+    #[test]
+    fn counts_items() {
+        let s = stream::iter(0..3).timing("ticks");
+        let items: Vec<_> = block_on(s.collect());
+        assert_eq!(items, vec![0, 1, 2]);
+    }
+}